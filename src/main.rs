@@ -4,13 +4,91 @@ use std::io::{self, Write};
 enum Token {
     Number(f64),
     Operator(char),
+    Function(String),
     LeftParen,
     RightParen,
 }
 
-fn tokenize(input: &str) -> Vec<Token> {
+#[derive(Debug, Clone, PartialEq)]
+enum CalcError {
+    InvalidCharacter { ch: char, pos: usize },
+    InvalidNumber(String),
+    MismatchedParen,
+    NotEnoughOperands(char),
+    EmptyExpression,
+    DivisionByZero,
+    UnexpectedToken,
+}
+
+impl std::fmt::Display for CalcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalcError::InvalidCharacter { ch, pos } => {
+                write!(f, "invalid character '{}' at position {}", ch, pos)
+            }
+            CalcError::InvalidNumber(s) => write!(f, "invalid number: {}", s),
+            CalcError::MismatchedParen => write!(f, "mismatched parenthesis"),
+            CalcError::NotEnoughOperands(op) => {
+                write!(f, "not enough operands for operator '{}'", op)
+            }
+            CalcError::EmptyExpression => write!(f, "empty expression"),
+            CalcError::DivisionByZero => write!(f, "division by zero"),
+            CalcError::UnexpectedToken => write!(f, "unexpected token"),
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+/// Looks up a named function (as accepted by `tokenize`) and returns the
+/// single-argument `f64` implementation backing it.
+fn get_function(name: &str) -> Option<fn(f64) -> f64> {
+    match name {
+        "sqrt" => Some(f64::sqrt),
+        "sin" => Some(f64::sin),
+        "cos" => Some(f64::cos),
+        "tan" => Some(f64::tan),
+        "ln" => Some(f64::ln),
+        "log10" => Some(f64::log10),
+        "abs" => Some(f64::abs),
+        "exp" => Some(f64::exp),
+        "floor" => Some(f64::floor),
+        "ceil" => Some(f64::ceil),
+        _ => None,
+    }
+}
+
+/// Looks up a read-only constant, resolving `ans` against the calculator's
+/// running memory so it can change from one line to the next.
+fn get_constant(name: &str, ans: f64) -> Option<f64> {
+    match name {
+        "pi" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        "ans" => Some(ans),
+        _ => None,
+    }
+}
+
+/// True once `tokens` ends in something a binary operator can apply to,
+/// i.e. the next `+`/`-` should be read as binary rather than unary.
+fn ends_with_value(tokens: &[Token]) -> bool {
+    matches!(tokens.last(), Some(Token::Number(_)) | Some(Token::RightParen))
+}
+
+/// Pushes a value-starting token (`Number`, `LeftParen`, or `Function`),
+/// first inserting an implicit `*` if it directly follows another value,
+/// e.g. `2(3+4)`, `(1+2)(3+4)`, or `3sin(0)`.
+fn push_value_start(tokens: &mut Vec<Token>, token: Token) {
+    if ends_with_value(tokens) {
+        tokens.push(Token::Operator('*'));
+    }
+    tokens.push(token);
+}
+
+fn tokenize(input: &str, ans: f64) -> Result<Vec<Token>, CalcError> {
     let mut tokens = Vec::new();
     let mut chars = input.chars().peekable();
+    let mut pos = 0usize;
 
     while let Some(&c) = chars.peek() {
         match c {
@@ -21,48 +99,89 @@ fn tokenize(input: &str) -> Vec<Token> {
                     if c.is_ascii_digit() || c == '.' {
                         number.push(c);
                         chars.next();
+                        pos += 1;
                     } else {
                         break;
                     }
                 }
 
-                if let Ok(n) = number.parse::<f64>() {
-                    tokens.push(Token::Number(n));
-                } else {
-                    eprintln!("Invalid number: {}", number);
+                let n = number
+                    .parse::<f64>()
+                    .map_err(|_| CalcError::InvalidNumber(number.clone()))?;
+                push_value_start(&mut tokens, Token::Number(n));
+            }
+            '+' | '-' if !ends_with_value(&tokens) => {
+                // Unary sign: unary '+' is a no-op, unary '-' becomes its
+                // own higher-precedence, right-associative operator.
+                chars.next();
+                pos += 1;
+                if c == '-' {
+                    tokens.push(Token::Operator('~'));
                 }
             }
             '+' | '-' | '*' | '/' | '^' => {
                 tokens.push(Token::Operator(c));
                 chars.next();
+                pos += 1;
             }
             '(' => {
-                tokens.push(Token::LeftParen);
+                push_value_start(&mut tokens, Token::LeftParen);
                 chars.next();
+                pos += 1;
             }
             ')' => {
                 tokens.push(Token::RightParen);
                 chars.next();
+                pos += 1;
             }
             ' ' | '\t' => {
                 // Skip whitespace
                 chars.next();
+                pos += 1;
             }
-            _ => {
-                eprintln!("Invalid character: {}", c);
-                chars.next();
+            c if c.is_alphabetic() => {
+                // Parse function/constant name (e.g. "log10" needs digits
+                // once the name is underway, not just leading letters)
+                let start = pos;
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() {
+                        name.push(c);
+                        chars.next();
+                        pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+
+                if get_function(&name).is_some() {
+                    push_value_start(&mut tokens, Token::Function(name));
+                } else if let Some(value) = get_constant(&name, ans) {
+                    push_value_start(&mut tokens, Token::Number(value));
+                } else {
+                    return Err(CalcError::InvalidCharacter {
+                        ch: name.chars().next().unwrap(),
+                        pos: start,
+                    });
+                }
             }
+            _ => return Err(CalcError::InvalidCharacter { ch: c, pos }),
         }
     }
-    tokens
+    Ok(tokens)
 }
 
-/// Higher precedence get evaluated first
+/// Higher precedence get evaluated first.
+///
+/// `~` (unary minus) is deliberately tied with `^` rather than placed above
+/// it: that's what makes `-2^2` parse as `-(2^2) = -4`, the conventional
+/// reading, while `2^-2` still parses as `2^(-2) = 0.25` since the `~`
+/// immediately to the right of `^` never gets a chance to pop it first.
 fn get_precedence(op: char) -> u32 {
     match op {
         '+' | '-' => 1,
         '*' | '/' => 2,
-        '^' => 3,
+        '^' | '~' => 3,
         _ => 0,
     }
 }
@@ -70,22 +189,27 @@ fn get_precedence(op: char) -> u32 {
 fn is_left_associative(op: char) -> bool {
     match op {
         '+' | '-' | '*' | '/' => true,
-        '^' => false,
+        '^' | '~' => false,
         _ => true,
     }
 }
 
 /// Shunting Yard Algorithm
-fn infix_to_postfix(tokens: Vec<Token>) -> Vec<Token> {
+fn infix_to_postfix(tokens: Vec<Token>) -> Result<Vec<Token>, CalcError> {
     let mut output_queue = Vec::new();
     let mut operator_stack = Vec::new();
 
     for token in tokens {
         match token {
             Token::Number(_) => output_queue.push(token),
+            Token::Function(_) => operator_stack.push(token),
             Token::Operator(op) => {
                 let current_precedence = get_precedence(op);
 
+                while let Some(Token::Function(_)) = operator_stack.last() {
+                    output_queue.push(operator_stack.pop().unwrap());
+                }
+
                 while let Some(Token::Operator(stack_op)) = operator_stack.last() {
                     let stack_precedence = get_precedence(*stack_op);
 
@@ -112,7 +236,12 @@ fn infix_to_postfix(tokens: Vec<Token>) -> Vec<Token> {
                 if let Some(Token::LeftParen) = operator_stack.last() {
                     operator_stack.pop();
                 } else {
-                    eprintln!("Mismatched parenthesis");
+                    return Err(CalcError::MismatchedParen);
+                }
+
+                // A function wrapping this group applies immediately
+                if let Some(Token::Function(_)) = operator_stack.last() {
+                    output_queue.push(operator_stack.pop().unwrap());
                 }
             }
         }
@@ -121,23 +250,26 @@ fn infix_to_postfix(tokens: Vec<Token>) -> Vec<Token> {
     // Pop any remaining operators from the stack
     while let Some(token) = operator_stack.pop() {
         if let Token::LeftParen = token {
-            eprintln!("Mismatched parenthesis");
-            continue;
+            return Err(CalcError::MismatchedParen);
         }
         output_queue.push(token);
     }
-    output_queue
+    Ok(output_queue)
 }
 
-fn evaluate_postfix(postfix: Vec<Token>) -> Result<f64, String> {
+fn evaluate_postfix(postfix: Vec<Token>) -> Result<f64, CalcError> {
     let mut stack = Vec::new();
 
     for token in postfix {
         match token {
             Token::Number(num) => stack.push(num),
+            Token::Operator('~') => {
+                let a = stack.pop().ok_or(CalcError::NotEnoughOperands('~'))?;
+                stack.push(-a);
+            }
             Token::Operator(op) => {
                 if stack.len() < 2 {
-                    return Err(format!("Not enough operands for operator {}", op));
+                    return Err(CalcError::NotEnoughOperands(op));
                 }
 
                 let b = stack.pop().unwrap();
@@ -149,23 +281,45 @@ fn evaluate_postfix(postfix: Vec<Token>) -> Result<f64, String> {
                     '*' => a * b,
                     '/' => {
                         if b == 0.0 {
-                            return Err("Division by zero".to_string());
+                            return Err(CalcError::DivisionByZero);
                         }
                         a / b
                     },
                     '^' => a.powf(b),
-                    _ => return Err(format!("Unknown operator {}", op)),
+                    _ => return Err(CalcError::UnexpectedToken),
                 };
 
                 stack.push(result);
             },
-            _=> return Err(format!("Unexpected token in postfix{:?}", token)),
+            Token::Function(name) => {
+                let arg = stack
+                    .pop()
+                    .ok_or_else(|| CalcError::NotEnoughOperands(name.chars().next().unwrap_or('?')))?;
+
+                let f = get_function(&name).ok_or(CalcError::UnexpectedToken)?;
+                stack.push(f(arg));
+            }
+            _=> return Err(CalcError::UnexpectedToken),
         }
     }
-    if stack.len() != 1 {
-        return Err("Invalid expression".to_string());
+
+    match stack.len() {
+        0 => Err(CalcError::EmptyExpression),
+        1 => Ok(stack.pop().unwrap()),
+        _ => Err(CalcError::UnexpectedToken),
+    }
+}
+
+/// Per-session calculator memory: just the previous line's result, resolved
+/// as the `ans` identifier.
+struct CalculatorState {
+    ans: f64,
+}
+
+impl CalculatorState {
+    fn new() -> Self {
+        Self { ans: 0.0 }
     }
-    Ok(stack.pop().unwrap())
 }
 
 fn main() {
@@ -173,6 +327,7 @@ fn main() {
     println!("Enter expression to calculate (or 'quit' to exit)");
 
     let mut input = String::new();
+    let mut state = CalculatorState::new();
 
     loop {
         print!("> ");
@@ -186,21 +341,25 @@ fn main() {
         if input.eq_ignore_ascii_case("quit") {
             break;
         }
-        let tokens = tokenize(input);
-        // Debugging
-        //println!("Tokens: {:?}", tokens);
-
-        let postfix = infix_to_postfix(tokens);
-        // Debugging
-        // println!("Postfix: {:?}", postfix);
 
-        match evaluate_postfix(postfix) {
-            Ok(result) => println!("res = {}", result),
-            Err(msg) => eprintln!("Error: {}", msg),
+        match evaluate_expression(input, &state) {
+            Ok(result) => {
+                println!("res = {}", result);
+                state.ans = result;
+            }
+            Err(err) => eprintln!("Error: {}", err),
         }
     }
 }
 
+/// Runs the full tokenize -> shunting-yard -> evaluate pipeline, stopping at
+/// the first `CalcError` so callers only have to match failure once.
+fn evaluate_expression(input: &str, state: &CalculatorState) -> Result<f64, CalcError> {
+    let tokens = tokenize(input, state.ans)?;
+    let postfix = infix_to_postfix(tokens)?;
+    evaluate_postfix(postfix)
+}
+
 #[cfg(test)]
 mod tests_tokens {
     use super::*;
@@ -208,7 +367,7 @@ mod tests_tokens {
     #[test]
     fn test_tokenize() {
         assert_eq!(
-            format!("{:?}", tokenize("2 + 2")),
+            format!("{:?}", tokenize("2 + 2", 0.0).unwrap()),
             format!(
                 "{:?}",
                 vec![Token::Number(2.0), Token::Operator('+'), Token::Number(2.0)]
@@ -219,16 +378,16 @@ mod tests_tokens {
     #[test]
     fn test_empty() {
         assert_eq!(
-            format!("{:?}", tokenize("")),
+            format!("{:?}", tokenize("", 0.0).unwrap()),
             format!("{:?}", Vec::<Token>::new())
         );
     }
 
     #[test]
     fn infix_to_postfix_test() {
-        let tokens = tokenize("2 + 2");
+        let tokens = tokenize("2 + 2", 0.0).unwrap();
         assert_eq!(
-            format!("{:?}", infix_to_postfix(tokens)),
+            format!("{:?}", infix_to_postfix(tokens).unwrap()),
             format!(
                 "{:?}",
                 vec![Token::Number(2.0), Token::Number(2.0), Token::Operator('+')]
@@ -238,9 +397,9 @@ mod tests_tokens {
 
     #[test]
     fn infix_to_postfix_test_with_precedence() {
-        let tokens = tokenize("2 + 2 * 3");
+        let tokens = tokenize("2 + 2 * 3", 0.0).unwrap();
         assert_eq!(
-            format!("{:?}", infix_to_postfix(tokens)),
+            format!("{:?}", infix_to_postfix(tokens).unwrap()),
             format!(
                 "{:?}",
                 vec![
@@ -256,9 +415,9 @@ mod tests_tokens {
 
     #[test]
     fn infix_to_postfix_test_with_parenthesis() {
-        let tokens = tokenize("(2 + 2) * 3");
+        let tokens = tokenize("(2 + 2) * 3", 0.0).unwrap();
         assert_eq!(
-            format!("{:?}", infix_to_postfix(tokens)),
+            format!("{:?}", infix_to_postfix(tokens).unwrap()),
             format!(
                 "{:?}",
                 vec![
@@ -271,4 +430,92 @@ mod tests_tokens {
             )
         )
     }
+
+    /// Runs the full pipeline for a one-off expression with no `ans` memory.
+    fn eval(input: &str) -> f64 {
+        let state = CalculatorState::new();
+        evaluate_expression(input, &state).unwrap()
+    }
+
+    #[test]
+    fn function_tokens_round_trip() {
+        assert!((eval("sqrt(4)") - 2.0).abs() < 1e-9);
+        assert!((eval("sin(0)") - 0.0).abs() < 1e-9);
+        assert!((eval("cos(0)") - 1.0).abs() < 1e-9);
+        assert!((eval("tan(0)") - 0.0).abs() < 1e-9);
+        assert!((eval("ln(1)") - 0.0).abs() < 1e-9);
+        assert!((eval("log10(100)") - 2.0).abs() < 1e-9);
+        assert!((eval("abs(-3)") - 3.0).abs() < 1e-9);
+        assert!((eval("exp(0)") - 1.0).abs() < 1e-9);
+        assert!((eval("floor(1.9)") - 1.0).abs() < 1e-9);
+        assert!((eval("ceil(1.1)") - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unary_minus_precedence() {
+        assert!((eval("-3 + 4") - 1.0).abs() < 1e-9);
+        assert!((eval("2 * -5") - (-10.0)).abs() < 1e-9);
+        assert!((eval("-(1 + 2)") - (-3.0)).abs() < 1e-9);
+        assert!((eval("5 - -3") - 8.0).abs() < 1e-9);
+        // `-` ties with `^` so it reads as the conventional -(2^2) = -4,
+        // while a `-` to the right of `^` still binds the exponent itself.
+        assert!((eval("-2^2") - (-4.0)).abs() < 1e-9);
+        assert!((eval("2^-2") - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn calc_error_variants() {
+        assert!(matches!(
+            tokenize("2 + @", 0.0),
+            Err(CalcError::InvalidCharacter { ch: '@', .. })
+        ));
+        assert!(matches!(
+            tokenize("1.2.3", 0.0),
+            Err(CalcError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            infix_to_postfix(tokenize("(2 + 2", 0.0).unwrap()),
+            Err(CalcError::MismatchedParen)
+        ));
+        assert!(matches!(
+            evaluate_postfix(vec![Token::Operator('+')]),
+            Err(CalcError::NotEnoughOperands('+'))
+        ));
+        assert!(matches!(
+            evaluate_postfix(vec![]),
+            Err(CalcError::EmptyExpression)
+        ));
+        assert!(matches!(
+            evaluate_postfix(vec![
+                Token::Number(1.0),
+                Token::Number(0.0),
+                Token::Operator('/')
+            ]),
+            Err(CalcError::DivisionByZero)
+        ));
+        assert!(matches!(
+            evaluate_postfix(vec![Token::LeftParen]),
+            Err(CalcError::UnexpectedToken)
+        ));
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        assert!((eval("2(3+4)") - 14.0).abs() < 1e-9);
+        assert!((eval("(1+2)(3+4)") - 21.0).abs() < 1e-9);
+        assert!((eval("3sin(0)") - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn constants_and_ans_memory() {
+        assert!((eval("pi") - std::f64::consts::PI).abs() < 1e-9);
+        assert!((eval("e") - std::f64::consts::E).abs() < 1e-9);
+
+        // Simulate two REPL lines: the second reads `ans` from the first.
+        let mut state = CalculatorState::new();
+        let first = evaluate_expression("2*pi", &state).unwrap();
+        state.ans = first;
+        let second = evaluate_expression("ans/4", &state).unwrap();
+        assert!((second - first / 4.0).abs() < 1e-9);
+    }
 }